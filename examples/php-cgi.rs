@@ -1,54 +1,48 @@
 extern crate tiny_http;
 
-/**!
-
-A web server that redirects every request to a PHP script.
-
-Usage: php-cgi <php-script-path>
-
-*/
+// A web server that redirects every request to a PHP script.
+//
+// Usage: php-cgi <php-script-path>
 
 fn handle(rq: tiny_http::Request, script: &str) {
     use std::process::Command;
-    use std::io::Write;
 
     let php = Command::new("php-cgi")
         .arg(script)
         //.stdin(Ignored)
         //.extra_io(Ignored)
         .env("AUTH_TYPE", "")
-        .env("CONTENT_LENGTH", format!("{}", rq.get_body_length().unwrap_or(0)))
+        .env("CONTENT_LENGTH", rq.get_body_length().unwrap_or(0).to_string())
         .env("CONTENT_TYPE", "")
         .env("GATEWAY_INTERFACE", "CGI/1.1")
         .env("PATH_INFO", "")
         .env("PATH_TRANSLATED", "")
-        .env("QUERY_STRING", format!("{}", rq.get_url()))
-        .env("REMOTE_ADDR", format!("{}", rq.get_remote_addr()))
+        .env("QUERY_STRING", rq.get_url())
+        .env("REMOTE_ADDR", rq.get_remote_addr().to_string())
         .env("REMOTE_HOST", "")
         .env("REMOTE_IDENT", "")
         .env("REMOTE_USER", "")
-        .env("REQUEST_METHOD", format!("{}", rq.get_method()))
+        .env("REQUEST_METHOD", rq.get_method().to_string())
         .env("SCRIPT_NAME", script)
         .env("SERVER_NAME", "tiny-http php-cgi example")
-        .env("SERVER_PORT", format!("{}", rq.get_remote_addr().port()))
+        .env("SERVER_PORT", rq.get_remote_addr().port().to_string())
         .env("SERVER_PROTOCOL", "HTTP/1.1")
         .env("SERVER_SOFTWARE", "tiny-http php-cgi example")
         .output()
         .unwrap();
 
 
-    // note: this is not a good implementation
-    // cgi returns the status code in the headers ; also many headers will be missing
-    //  in the response
     match php.status {
         status if status.success() => {
-            let mut writer = rq.into_writer();
-            let mut writer: &mut Write = &mut *writer;
+            use std::io::Cursor;
 
-            (write!(writer, "HTTP/1.1 200 OK\r\n")).unwrap();
-            (write!(writer, "{}", String::from_utf8(php.stdout.clone()).unwrap())).unwrap();
-
-            writer.flush().unwrap();
+            match tiny_http::Response::from_cgi(Cursor::new(php.stdout)) {
+                Ok(response) => rq.respond(response),
+                Err(err) => {
+                    println!("Malformed CGI response: {:?}", err);
+                    rq.respond(tiny_http::Response::new_empty(tiny_http::StatusCode(500)));
+                }
+            }
         },
         _ => {
             println!("Error in script execution: {}", String::from_utf8(php.stderr.clone()).unwrap());
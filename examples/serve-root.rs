@@ -3,6 +3,8 @@ use std::fs;
 
 extern crate tiny_http;
 
+use tiny_http::{ContentType, Header, RangeError, Response, StatusCode};
+
 fn get_content_type(path: &Path) -> &'static str {
     let extension = match path.extension() {
         None => return "text/plain",
@@ -22,6 +24,11 @@ fn get_content_type(path: &Path) -> &'static str {
     }
 }
 
+/// Boundary between parts of a `multipart/byteranges` response. Fixed
+/// rather than random since this example has no RNG dependency; a real
+/// server would want one unlikely to appear in the file itself.
+const MULTIPART_BOUNDARY: &str = "THIS_STRING_SEPARATES_TINY_HTTP_RANGES";
+
 fn main() {
     let server = tiny_http::ServerBuilder::new().with_random_port().build().unwrap();
     let port = server.get_server_addr().port();
@@ -37,23 +44,63 @@ fn main() {
 
         let url = rq.get_url().to_string();
         let path = Path::new(&url);
-        let file = fs::File::open(&path);
 
-        if file.is_ok() {
-            let response = tiny_http::Response::from_file(file.unwrap());
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(_) => {
+                rq.respond(Response::new_empty(StatusCode(404)));
+                continue;
+            }
+        };
 
-            let response = response.with_header(
-                tiny_http::Header {
-                    field: "Content-Type".parse().unwrap(),
-                    value: get_content_type(&path).to_string(),
-                }
-            );
+        let total_len = match file.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(_) => {
+                rq.respond(Response::new_empty(StatusCode(500)));
+                continue;
+            }
+        };
 
-            rq.respond(response);
+        let content_type = get_content_type(path).to_string();
+        let range = rq.headers().iter().find(|h| h.field().equiv("Range")).map(|h| h.value().to_string());
 
-        } else {
-            let rep = tiny_http::Response::new_empty(tiny_http::StatusCode(404));
-            rq.respond(rep);
+        let range = match range {
+            None => None,
+            Some(value) => match tiny_http::parse_range_header(&value, total_len) {
+                Ok(ranges) => Some(ranges),
+                Err(RangeError::NotBytesRange) => None,
+                Err(RangeError::Unsatisfiable) | Err(RangeError::TooManyRanges) => {
+                    rq.respond(tiny_http::unsatisfiable_response(total_len));
+                    continue;
+                }
+            }
+        };
+
+        match range {
+            None => {
+                let response = Response::from_file(file)
+                    .with_typed_header(ContentType(content_type))
+                    .with_header(Header::from_bytes("Accept-Ranges".parse().unwrap(), "bytes").unwrap())
+                    .with_compression_default(&rq);
+                rq.respond(response);
+            }
+            Some(ref ranges) if ranges.len() == 1 => {
+                match tiny_http::single_range_response(file, ranges[0], total_len) {
+                    Ok(response) => rq.respond(response.with_typed_header(ContentType(content_type))),
+                    Err(_) => rq.respond(Response::new_empty(StatusCode(500))),
+                }
+            }
+            Some(ref ranges) => {
+                match tiny_http::multipart_byteranges_body(file, ranges, total_len, &content_type, MULTIPART_BOUNDARY) {
+                    Ok(body) => {
+                        let response = Response::from_data(body)
+                            .with_status_code(206)
+                            .with_typed_header(ContentType(format!("multipart/byteranges; boundary={}", MULTIPART_BOUNDARY)));
+                        rq.respond(response);
+                    }
+                    Err(_) => rq.respond(Response::new_empty(StatusCode(500))),
+                }
+            }
         }
     }
 }
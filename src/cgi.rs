@@ -0,0 +1,158 @@
+// Copyright 2015 The tiny-http Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parses the raw stdout of a CGI process into a `Response`, honoring the
+//! script-provided `Status:` and `Location:` headers instead of always
+//! answering `200 OK`.
+
+use std::io::{self, Read};
+
+use common::{Header, StatusCode};
+use Response;
+
+/// Why `Response::from_cgi` couldn't build a response.
+#[derive(Debug)]
+pub enum CgiError {
+    /// Reading the process' stdout failed.
+    Io(io::Error),
+    /// The output has no blank line separating headers from the body.
+    MissingHeaderTerminator,
+    /// A header line isn't `Name: value`, or a `Status:` value doesn't
+    /// start with a numeric code.
+    MalformedHeader,
+}
+
+impl From<io::Error> for CgiError {
+    fn from(err: io::Error) -> CgiError {
+        CgiError::Io(err)
+    }
+}
+
+/// Parses `output` (the full stdout of a CGI process) into a status code,
+/// headers, and body, per RFC 3875 section 6.
+fn parse_cgi_output(output: &[u8]) -> Result<(StatusCode, Vec<Header>, &[u8]), CgiError> {
+    let separator = find_header_terminator(output).ok_or(CgiError::MissingHeaderTerminator)?;
+    let (header_block, rest) = output.split_at(separator.0);
+    let body = &rest[separator.1..];
+
+    let header_block = String::from_utf8_lossy(header_block);
+
+    let mut status_code = None;
+    let mut headers = Vec::new();
+    let mut location = None;
+
+    for line in header_block.split("\r\n").flat_map(|l| l.split('\n')) {
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+
+        let header: Header = line.parse().map_err(|_| CgiError::MalformedHeader)?;
+
+        if header.field().equiv("Status") {
+            status_code = Some(parse_status_header(header.value()).ok_or(CgiError::MalformedHeader)?);
+        } else if header.field().equiv("Location") {
+            location = Some(header.value().to_string());
+            headers.push(header);
+        } else {
+            headers.push(header);
+        }
+    }
+
+    // A CGI script that only sets `Location` (no `Status`) is issuing a
+    // redirect; default it to 302 like other CGI gateways do.
+    let status_code = match status_code {
+        Some(code) => code,
+        None if location.is_some() => StatusCode(302),
+        None => StatusCode(200),
+    };
+
+    Ok((status_code, headers, body))
+}
+
+/// Parses a CGI `Status:` value, e.g. `"404 Not Found"` or bare `"404"`.
+fn parse_status_header(value: &str) -> Option<StatusCode> {
+    let code_str = value.trim().split(' ').next().unwrap();
+    code_str.parse::<u16>().ok().map(StatusCode::from_u16)
+}
+
+/// Finds the first blank-line header/body separator, returning
+/// `(header_block_len, separator_len)` so callers can skip `\n\n` or
+/// `\r\n\r\n` alike.
+fn find_header_terminator(output: &[u8]) -> Option<(usize, usize)> {
+    let crlf = find_subslice(output, b"\r\n\r\n").map(|pos| (pos, 4));
+    let lf = find_subslice(output, b"\n\n").map(|pos| (pos, 2));
+
+    match (crlf, lf) {
+        (Some(crlf), Some(lf)) => Some(if crlf.0 < lf.0 { crlf } else { lf }),
+        (crlf, lf) => crlf.or(lf),
+    }
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || haystack.len() < needle.len() {
+        return None;
+    }
+
+    (0..=haystack.len() - needle.len()).find(|&i| &haystack[i..i + needle.len()] == needle)
+}
+
+impl Response<io::Cursor<Vec<u8>>> {
+    /// Builds a `Response` from the raw stdout of a CGI process, parsing
+    /// out the `Status:`/`Location:` headers the script emitted instead
+    /// of hard-coding `200 OK`.
+    pub fn from_cgi<R: Read>(mut reader: R) -> Result<Response<io::Cursor<Vec<u8>>>, CgiError> {
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        let (status_code, headers, body) = parse_cgi_output(&output)?;
+        let body = body.to_vec();
+        let body_len = body.len();
+
+        Ok(Response::new(status_code, headers, io::Cursor::new(body), Some(body_len), None))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_cgi_output;
+    use common::StatusCode;
+
+    #[test]
+    fn defaults_to_200() {
+        let (status, headers, body) = parse_cgi_output(b"Content-Type: text/plain\n\nhello").unwrap();
+        assert_eq!(status, StatusCode(200));
+        assert_eq!(headers.len(), 1);
+        assert_eq!(body, b"hello");
+    }
+
+    #[test]
+    fn honors_status_header() {
+        let (status, _, body) = parse_cgi_output(b"Status: 404 Not Found\r\n\r\noops").unwrap();
+        assert_eq!(status, StatusCode(404));
+        assert_eq!(body, b"oops");
+    }
+
+    #[test]
+    fn location_without_status_defaults_to_302() {
+        let (status, headers, _) = parse_cgi_output(b"Location: /elsewhere\n\n").unwrap();
+        assert_eq!(status, StatusCode(302));
+        assert!(headers.iter().any(|h| h.field().equiv("Location") && h.value() == "/elsewhere"));
+    }
+
+    #[test]
+    fn missing_terminator_is_an_error() {
+        assert!(parse_cgi_output(b"Content-Type: text/plain").is_err());
+    }
+}
@@ -12,10 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::ascii::AsciiExt;
+use std::error::Error;
 use std::fmt::{self, Display, Formatter};
 use std::str::{FromStr};
-use std::cmp::Ordering;
 
 /// Status code of a request or response.
 #[derive(Eq, PartialEq, Clone, Debug, Ord, PartialOrd)]
@@ -124,6 +123,48 @@ impl From<u32> for StatusCode {
     }
 }
 
+/// A header field name, header value, or method failed to conform to the
+/// HTTP token / field-value grammar (RFC 7230 section 3.2).
+///
+/// In particular, header values must never contain a raw CR or LF: letting
+/// one through would allow a caller-supplied value to smuggle a second
+/// header (or split the response) into the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpGrammarError;
+
+impl Display for HttpGrammarError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(formatter, "value does not conform to the HTTP grammar")
+    }
+}
+
+impl Error for HttpGrammarError {
+    fn description(&self) -> &str {
+        "value does not conform to the HTTP grammar"
+    }
+}
+
+/// `tchar`, per RFC 7230 section 3.2.6: the set of characters allowed in a
+/// header field name or a method.
+fn is_tchar(b: u8) -> bool {
+    matches!(b,
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' |
+        b'^' | b'_' | b'`' | b'|' | b'~' |
+        b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z')
+}
+
+/// A non-empty token made up entirely of `tchar`s.
+fn is_valid_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(is_tchar)
+}
+
+/// A header value may never contain a raw CR, LF, or NUL (see
+/// `HttpGrammarError`); every other byte, including other control
+/// bytes, is accepted.
+fn is_valid_header_value(s: &str) -> bool {
+    s.bytes().all(|b| b != b'\r' && b != b'\n' && b != 0)
+}
+
 /// Represents a HTTP header.
 ///
 /// The easiest way to create a `Header` object is to call `parse`.
@@ -133,14 +174,43 @@ impl From<u32> for StatusCode {
 /// ```
 #[derive(Debug, Clone)]
 pub struct Header {
-    pub field: HeaderField,
-    pub value: String,
+    field: HeaderField,
+    value: String,
+}
+
+impl Header {
+    /// Builds a `Header` from an already-parsed field and a value,
+    /// validating the value against the HTTP grammar (see
+    /// `HttpGrammarError`).
+    ///
+    /// Together with `parse` (which validates the value the same way),
+    /// this is the only way to build a `Header` from outside this module;
+    /// `field` and `value` are otherwise private so a caller can't mutate
+    /// a validated `Header` back into an invalid one after the fact.
+    pub fn from_bytes(field: HeaderField, value: &str) -> Result<Header, HttpGrammarError> {
+        if !is_valid_header_value(value) {
+            return Err(HttpGrammarError);
+        }
+
+        Ok(Header {
+            field,
+            value: value.to_string(),
+        })
+    }
+
+    pub fn field(&self) -> &HeaderField {
+        &self.field
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
 }
 
 impl FromStr for Header {
-    type Err = ();
+    type Err = HttpGrammarError;
 
-    fn from_str(input: &str) -> Result<Header, ()> {
+    fn from_str(input: &str) -> Result<Header, HttpGrammarError> {
         let mut elems = input.splitn(2, ':');
 
         let field = elems.next();
@@ -148,18 +218,15 @@ impl FromStr for Header {
 
         let (field, value) = match (field, value) {
             (Some(f), Some(v)) => (f, v),
-            _ => return Err(())
+            _ => return Err(HttpGrammarError)
         };
 
-        let field = match FromStr::from_str(field) {
+        let field: HeaderField = match FromStr::from_str(field) {
             Ok(f) => f,
-            _ => return Err(())
+            Err(_) => return Err(HttpGrammarError)
         };
 
-        Ok(Header {
-            field: field,
-            value: value.to_string(),
-        })
+        Header::from_bytes(field, value.trim())
     }
 }
 
@@ -176,8 +243,8 @@ impl Display for Header {
 pub struct HeaderField(String);
 
 impl HeaderField {
-    pub fn as_str<'a>(&'a self) -> &'a String {
-        match self { &HeaderField(ref s) => s }
+    pub fn as_str(&self) -> &String {
+        match self { HeaderField(s) => s }
     }
 
     pub fn equiv(&self, other: &'static str) -> bool {
@@ -186,10 +253,16 @@ impl HeaderField {
 }
 
 impl FromStr for HeaderField {
-    type Err = ();
+    type Err = HttpGrammarError;
+
+    fn from_str(s: &str) -> Result<HeaderField, HttpGrammarError> {
+        let s = s.trim();
+
+        if !is_valid_token(s) {
+            return Err(HttpGrammarError);
+        }
 
-    fn from_str(s: &str) -> Result<HeaderField, ()> {
-        Ok(HeaderField(s.trim().to_string()))
+        Ok(HeaderField(s.to_string()))
     }
 }
 
@@ -219,7 +292,7 @@ pub struct Method(String);
 
 impl Method {
     fn as_str(&self) -> &String {
-        match self { &Method(ref s) => s }
+        match self { Method(s) => s }
     }
 
     pub fn equiv(&self, other: &'static str) -> bool {
@@ -228,9 +301,13 @@ impl Method {
 }
 
 impl FromStr for Method {
-    type Err = ();
+    type Err = HttpGrammarError;
+
+    fn from_str(s: &str) -> Result<Method, HttpGrammarError> {
+        if !is_valid_token(s) {
+            return Err(HttpGrammarError);
+        }
 
-    fn from_str(s: &str) -> Result<Method, ()> {
         Ok(Method(s.to_string()))
     }
 }
@@ -251,7 +328,10 @@ impl Eq for Method {}
 
 
 /// HTTP version (usually 1.0 or 1.1).
-#[derive(Debug, Clone, PartialEq, Eq, Ord)]
+///
+/// Ordering compares `major` then `minor`, which the derived impl already
+/// does field-by-field.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct HTTPVersion(pub usize, pub usize);
 
 impl Display for HTTPVersion {
@@ -261,30 +341,17 @@ impl Display for HTTPVersion {
     }
 }
 
-impl PartialOrd for HTTPVersion {
-    fn partial_cmp(&self, other: &HTTPVersion) -> Option<Ordering> {
-        let (my_major, my_minor) = match self { &HTTPVersion(m, n) => (m, n) };
-        let (other_major, other_minor) = match other { &HTTPVersion(m, n) => (m, n) };
-
-        if my_major != other_major {
-            return my_major.partial_cmp(&other_major)
-        }
-
-        my_minor.partial_cmp(&other_minor)
-    }
-}
-
 
 #[cfg(test)]
 mod test {
-    use super::Header;
+    use super::{Header, HeaderField, Method};
 
     #[test]
     fn test_parse_header() {
         let header: Header = "Content-Type: text/html".parse().unwrap();
 
-        assert!(header.field.equiv(&"content-type"));
-        assert!(header.value == "text/html");
+        assert!(header.field().equiv("content-type"));
+        assert!(header.value() == "text/html");
 
         assert!("hello world".parse::<Header>().is_err());
     }
@@ -293,7 +360,36 @@ mod test {
     fn test_parse_header_with_doublecolon() {
         let header: Header = "Time: 20: 34".parse().unwrap();
 
-        assert!(header.field.equiv(&"time"));
-        assert!(header.value == "20: 34");
+        assert!(header.field().equiv("time"));
+        assert!(header.value() == "20: 34");
+    }
+
+    #[test]
+    fn rejects_empty_header_field_name() {
+        assert!("".parse::<HeaderField>().is_err());
+    }
+
+    #[test]
+    fn rejects_separators_in_header_field_name() {
+        assert!("Foo: Bar".parse::<HeaderField>().is_err());
+        assert!("Foo/Bar".parse::<HeaderField>().is_err());
+    }
+
+    #[test]
+    fn rejects_crlf_smuggled_through_header_value() {
+        assert!("Content-Type: text/html\r\nX-Injected: evil".parse::<Header>().is_err());
+        assert!(Header::from_bytes("X-Foo".parse().unwrap(), "bar\r\nX-Injected: evil").is_err());
+    }
+
+    #[test]
+    fn accepts_valid_header_value_with_embedded_colon() {
+        assert!(Header::from_bytes("Time".parse().unwrap(), "20: 34").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_method() {
+        assert!("GET".parse::<Method>().is_ok());
+        assert!("GET /".parse::<Method>().is_err());
+        assert!("".parse::<Method>().is_err());
     }
 }
@@ -0,0 +1,183 @@
+// Copyright 2015 The tiny-http Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Negotiates a response body encoding against a request's `Accept-Encoding`
+//! header and wraps the response's reader in the matching compressor.
+
+use std::io::Read;
+
+use brotli::CompressorReader as BrotliEncoder;
+use flate2::read::{DeflateEncoder, GzEncoder};
+use flate2::Compression;
+
+use common::Header;
+use typed_header::AcceptEncoding;
+use Request;
+use Response;
+
+/// A content coding that `with_compression` knows how to produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Gzip,
+    Deflate,
+    Br,
+}
+
+impl Encoding {
+    fn token(&self) -> &'static str {
+        match *self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Br => "br",
+        }
+    }
+
+    fn from_token(token: &str) -> Option<Encoding> {
+        match token {
+            "gzip" | "x-gzip" => Some(Encoding::Gzip),
+            "deflate" => Some(Encoding::Deflate),
+            "br" => Some(Encoding::Br),
+            _ => None,
+        }
+    }
+}
+
+/// Below this many bytes, compressing the body costs more than it saves.
+pub const DEFAULT_MIN_COMPRESS_SIZE: usize = 860;
+
+/// Picks the best encoding this crate supports out of an already-parsed
+/// `Accept-Encoding` header, or `None` if the client only accepts
+/// `identity` (or didn't send the header, or rejected everything we offer).
+fn negotiate(accept_encoding: &AcceptEncoding) -> Option<Encoding> {
+    let identity_explicitly_rejected = accept_encoding.0
+        .iter()
+        .any(|item| (item.coding == "identity" || item.coding == "*") && item.quality == 0.0);
+
+    let mut best: Option<(Encoding, f32)> = None;
+
+    for item in &accept_encoding.0 {
+        if item.quality <= 0.0 {
+            continue;
+        }
+
+        if let Some(encoding) = Encoding::from_token(&item.coding) {
+            let better = match best {
+                Some((_, best_quality)) => item.quality > best_quality,
+                None => true,
+            };
+
+            if better {
+                best = Some((encoding, item.quality));
+            }
+        }
+    }
+
+    if best.is_none() && identity_explicitly_rejected {
+        // Nothing we support was accepted and identity is off the table.
+        // tiny-http has no content-negotiation failure response, so no 406
+        // is sent: we return None and compression is simply skipped.
+        return None;
+    }
+
+    best.map(|(encoding, _)| encoding)
+}
+
+impl<R> Response<R>
+where
+    R: Read + Send + 'static,
+{
+    /// Compresses the response body if the request's `Accept-Encoding`
+    /// header names a codec we support and the body is at least
+    /// `min_size` bytes, replacing any known `Content-Length` with
+    /// chunked transfer-encoding since the compressed size isn't known
+    /// up front.
+    pub fn with_compression(self, request: &Request, min_size: usize) -> Response<Box<dyn Read + Send>> {
+        let too_small = self
+            .data_length()
+            .map(|len| len < min_size)
+            .unwrap_or(false);
+
+        let encoding = if too_small {
+            None
+        } else {
+            match request.header::<AcceptEncoding>() {
+                Some(Ok(accept_encoding)) => negotiate(&accept_encoding),
+                // Malformed Accept-Encoding or none sent: skip compression
+                // rather than guess, since AcceptEncoding::parse rejects a
+                // bad `q=` outright instead of silently defaulting to 1.0.
+                _ => None,
+            }
+        };
+
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => return self.boxed(),
+        };
+
+        // Grab everything but the body before `into_reader` consumes `self`.
+        let status_code = self.status_code();
+        let headers = self.headers().to_vec();
+        let reader = self.into_reader();
+
+        let data: Box<dyn Read + Send> = match encoding {
+            Encoding::Gzip => Box::new(GzEncoder::new(reader, Compression::default())),
+            Encoding::Deflate => Box::new(DeflateEncoder::new(reader, Compression::default())),
+            Encoding::Br => Box::new(BrotliEncoder::new(reader, 4096, 5, 22)),
+        };
+
+        Response::new(status_code, headers, data, None, None)
+            .with_header(Header::from_bytes("Content-Encoding".parse().unwrap(), encoding.token()).unwrap())
+    }
+
+    /// Equivalent to `with_compression` with `DEFAULT_MIN_COMPRESS_SIZE`.
+    pub fn with_compression_default(self, request: &Request) -> Response<Box<dyn Read + Send>> {
+        self.with_compression(request, DEFAULT_MIN_COMPRESS_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{negotiate, Encoding};
+    use typed_header::{AcceptEncoding, TypedHeader};
+
+    fn parse(value: &str) -> AcceptEncoding {
+        AcceptEncoding::parse(value).unwrap()
+    }
+
+    #[test]
+    fn picks_highest_quality() {
+        assert_eq!(negotiate(&parse("gzip;q=0.5, br;q=0.8, deflate")), Some(Encoding::Deflate));
+    }
+
+    #[test]
+    fn ignores_unsupported_tokens() {
+        assert_eq!(negotiate(&parse("sdch, gzip")), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn zero_quality_is_rejected() {
+        assert_eq!(negotiate(&parse("gzip;q=0, br;q=0.1")), Some(Encoding::Br));
+        assert_eq!(negotiate(&parse("gzip;q=0")), None);
+    }
+
+    #[test]
+    fn identity_only_means_no_compression() {
+        assert_eq!(negotiate(&parse("identity")), None);
+    }
+
+    #[test]
+    fn empty_header_means_no_compression() {
+        assert_eq!(negotiate(&parse("")), None);
+    }
+}
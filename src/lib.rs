@@ -0,0 +1,42 @@
+// Copyright 2015 The tiny-http Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Low level HTTP server library.
+
+extern crate brotli;
+extern crate flate2;
+
+mod cgi;
+mod common;
+mod compression;
+mod range;
+mod request;
+mod response;
+mod server;
+mod typed_header;
+
+pub use cgi::CgiError;
+pub use common::{Header, HeaderField, HTTPVersion, HttpGrammarError, Method, StatusCode};
+pub use compression::{Encoding, DEFAULT_MIN_COMPRESS_SIZE};
+pub use range::{
+    multipart_byteranges_body, parse_range_header, single_range_response, unsatisfiable_response,
+    ByteRange, RangeError,
+};
+pub use request::Request;
+pub use response::Response;
+pub use server::{IncomingRequests, Server, ServerBuilder};
+pub use typed_header::{
+    AcceptEncoding, AcceptEncodingItem, Connection, ContentEncoding, ContentLength, ContentType,
+    Host, TransferEncoding, TypedHeader, TypedHeaderError,
+};
@@ -0,0 +1,249 @@
+// Copyright 2015 The tiny-http Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and serving of `Range: bytes=...` requests, so that a file can
+//! be answered with `206 Partial Content` (or `416`) instead of always
+//! being sent whole through `Response::from_file`.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use common::{Header, StatusCode};
+use Response;
+
+/// A client asking for more discrete ranges than this is almost certainly
+/// abusive (each range allocates its own buffer); reject the whole
+/// `Range` header rather than honor it.
+pub const MAX_RANGES: usize = 32;
+
+/// One inclusive `start..=end` byte range, already resolved against the
+/// resource's total length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn byte_len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Why a `Range` header couldn't be honored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeError {
+    /// The header isn't `bytes=...` or its syntax is invalid; the caller
+    /// should ignore it and serve the full body.
+    NotBytesRange,
+    /// Every requested range falls outside `0..total`.
+    Unsatisfiable,
+    /// More than `MAX_RANGES` ranges were requested at once.
+    TooManyRanges,
+}
+
+/// Parses a `Range: bytes=...` header value against a known resource
+/// length, returning the ranges in request order.
+///
+/// Supports `start-end`, `start-` (open-ended) and `-suffix` (last N
+/// bytes) forms, per RFC 7233 section 2.1.
+pub fn parse_range_header(value: &str, total_len: u64) -> Result<Vec<ByteRange>, RangeError> {
+    let value = value.trim();
+    let specs = match value.strip_prefix("bytes=") {
+        Some(specs) => specs,
+        None => return Err(RangeError::NotBytesRange),
+    };
+
+    if specs.split(',').count() > MAX_RANGES {
+        return Err(RangeError::TooManyRanges);
+    }
+
+    let mut ranges = Vec::new();
+
+    for spec in specs.split(',') {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Err(RangeError::NotBytesRange);
+        }
+
+        let mut parts = spec.splitn(2, '-');
+        let start_str = parts.next().unwrap();
+        let end_str = match parts.next() {
+            Some(e) => e,
+            None => return Err(RangeError::NotBytesRange),
+        };
+
+        let range = if start_str.is_empty() {
+            // "-suffix": the last `suffix` bytes.
+            let suffix: u64 = end_str.parse().map_err(|_| RangeError::NotBytesRange)?;
+            if suffix == 0 || total_len == 0 {
+                continue;
+            }
+            let suffix = if suffix > total_len { total_len } else { suffix };
+            ByteRange {
+                start: total_len - suffix,
+                end: total_len - 1,
+            }
+        } else {
+            let start: u64 = start_str.parse().map_err(|_| RangeError::NotBytesRange)?;
+            let end = if end_str.is_empty() {
+                total_len.saturating_sub(1)
+            } else {
+                end_str.parse::<u64>().map_err(|_| RangeError::NotBytesRange)?
+            };
+
+            if start >= total_len || start > end {
+                continue;
+            }
+
+            ByteRange {
+                start,
+                end: if end >= total_len { total_len - 1 } else { end },
+            }
+        };
+
+        ranges.push(range);
+    }
+
+    if ranges.is_empty() {
+        Err(RangeError::Unsatisfiable)
+    } else {
+        Ok(ranges)
+    }
+}
+
+/// Builds the `416 Range Not Satisfiable` response for a rejected `Range`
+/// header.
+pub fn unsatisfiable_response(total_len: u64) -> Response<io::Empty> {
+    Response::new_empty(StatusCode(416))
+        .with_header(Header::from_bytes("Content-Range".parse().unwrap(), &format!("bytes */{}", total_len)).unwrap())
+}
+
+/// Serves a single satisfiable range of `reader` (which must be
+/// `total_len` bytes long) as a `206 Partial Content` response.
+pub fn single_range_response<R>(mut reader: R, range: ByteRange, total_len: u64) -> io::Result<Response<io::Take<R>>>
+where
+    R: Read + Seek,
+{
+    reader.seek(SeekFrom::Start(range.start))?;
+    let limited = Read::take(reader, range.byte_len());
+
+    Ok(Response::new(
+        StatusCode(206),
+        vec![
+            Header::from_bytes(
+                "Content-Range".parse().unwrap(),
+                &format!("bytes {}-{}/{}", range.start, range.end, total_len),
+            ).unwrap(),
+            Header::from_bytes("Accept-Ranges".parse().unwrap(), "bytes").unwrap(),
+        ],
+        limited,
+        Some(range.byte_len() as usize),
+        None,
+    ))
+}
+
+/// Builds the `multipart/byteranges` body for a multi-range request, with
+/// the parts separated by the caller-supplied `boundary`; each part carries
+/// its own `Content-Range` and `Content-Type`.
+pub fn multipart_byteranges_body<R>(
+    mut reader: R,
+    ranges: &[ByteRange],
+    total_len: u64,
+    content_type: &str,
+    boundary: &str,
+) -> io::Result<Vec<u8>>
+where
+    R: Read + Seek,
+{
+    let mut body = Vec::new();
+
+    for range in ranges {
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+        body.extend_from_slice(
+            format!("Content-Range: bytes {}-{}/{}\r\n\r\n", range.start, range.end, total_len).as_bytes(),
+        );
+
+        reader.seek(SeekFrom::Start(range.start))?;
+        let mut part = vec![0u8; range.byte_len() as usize];
+        reader.read_exact(&mut part)?;
+        body.extend_from_slice(&part);
+
+        body.extend_from_slice(b"\r\n");
+    }
+
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    Ok(body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_range_header, ByteRange, RangeError};
+
+    #[test]
+    fn parses_start_end() {
+        assert_eq!(
+            parse_range_header("bytes=0-499", 1000).unwrap(),
+            vec![ByteRange { start: 0, end: 499 }]
+        );
+    }
+
+    #[test]
+    fn parses_open_ended() {
+        assert_eq!(
+            parse_range_header("bytes=500-", 1000).unwrap(),
+            vec![ByteRange { start: 500, end: 999 }]
+        );
+    }
+
+    #[test]
+    fn parses_suffix() {
+        assert_eq!(
+            parse_range_header("bytes=-500", 1000).unwrap(),
+            vec![ByteRange { start: 500, end: 999 }]
+        );
+    }
+
+    #[test]
+    fn parses_multiple_ranges() {
+        assert_eq!(
+            parse_range_header("bytes=0-99, 200-299", 1000).unwrap(),
+            vec![ByteRange { start: 0, end: 99 }, ByteRange { start: 200, end: 299 }]
+        );
+    }
+
+    #[test]
+    fn clamps_end_to_total_len() {
+        assert_eq!(
+            parse_range_header("bytes=900-2000", 1000).unwrap(),
+            vec![ByteRange { start: 900, end: 999 }]
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_start() {
+        assert_eq!(parse_range_header("bytes=2000-3000", 1000), Err(RangeError::Unsatisfiable));
+    }
+
+    #[test]
+    fn rejects_non_bytes_unit() {
+        assert_eq!(parse_range_header("items=0-1", 1000), Err(RangeError::NotBytesRange));
+    }
+
+    #[test]
+    fn rejects_too_many_ranges() {
+        let value = format!("bytes={}", vec!["0-0"; super::MAX_RANGES + 1].join(","));
+        assert_eq!(parse_range_header(&value, 1000), Err(RangeError::TooManyRanges));
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2015 The tiny-http Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+
+use common::{Header, HTTPVersion, Method};
+use Response;
+
+/// A request received by the server.
+///
+/// Call `respond` exactly once to answer it.
+pub struct Request {
+    remote_addr: SocketAddr,
+    method: Method,
+    url: String,
+    headers: Vec<Header>,
+    http_version: HTTPVersion,
+    body_length: Option<usize>,
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+}
+
+impl Request {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(method: Method, url: String, headers: Vec<Header>, http_version: HTTPVersion,
+               remote_addr: SocketAddr, body_length: Option<usize>,
+               reader: Box<dyn Read + Send>, writer: Box<dyn Write + Send>) -> Request {
+        Request {
+            remote_addr,
+            method,
+            url,
+            headers,
+            http_version,
+            body_length,
+            reader,
+            writer,
+        }
+    }
+
+    pub fn get_method(&self) -> &Method {
+        &self.method
+    }
+
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    pub fn http_version(&self) -> &HTTPVersion {
+        &self.http_version
+    }
+
+    pub fn get_remote_addr(&self) -> &SocketAddr {
+        &self.remote_addr
+    }
+
+    pub fn get_body_length(&self) -> Option<usize> {
+        self.body_length
+    }
+
+    /// Gives mutable access to the request's body.
+    pub fn as_reader(&mut self) -> &mut dyn Read {
+        &mut self.reader
+    }
+
+    /// Consumes the request and returns the writer its response must be
+    /// written to, for callers that want to bypass `respond`.
+    pub fn into_writer(self) -> Box<dyn Write + Send> {
+        self.writer
+    }
+
+    /// Answers the request with `response`, ignoring write errors (the
+    /// client may already have disconnected).
+    pub fn respond<R: Read>(self, response: Response<R>) {
+        let mut writer = self.writer;
+        let _ = response.write(&mut *writer, &self.http_version);
+    }
+}
+
+impl Debug for Request {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "Request({} {} from {})", self.method, self.url, self.remote_addr)
+    }
+}
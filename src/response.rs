@@ -0,0 +1,173 @@
+// Copyright 2015 The tiny-http Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::File;
+use std::io::{self, Empty, Read, Write};
+
+use common::{Header, HTTPVersion, StatusCode};
+
+/// A response that can be sent to a client in answer to a `Request`.
+///
+/// `R` is the type of the reader that produces the response's body.
+pub struct Response<R> where R: Read {
+    reader: R,
+    status_code: StatusCode,
+    headers: Vec<Header>,
+    data_length: Option<usize>,
+    chunked_threshold: Option<usize>,
+}
+
+impl<R> Response<R> where R: Read {
+    /// Builds a new `Response` from its raw parts.
+    ///
+    /// `data_length` should be `Some` whenever the exact size of `data` is
+    /// known ahead of time; otherwise the response falls back to
+    /// chunked transfer-encoding.
+    pub fn new(status_code: StatusCode, headers: Vec<Header>, data: R,
+               data_length: Option<usize>, chunked_threshold: Option<usize>) -> Response<R> {
+        Response {
+            reader: data,
+            status_code,
+            headers,
+            data_length,
+            chunked_threshold,
+        }
+    }
+
+    /// Adds a header, replacing any existing header with the same field
+    /// name (so `with_header` can't add e.g. a second `Set-Cookie` header;
+    /// `Response::new` keeps whatever header list it is given, duplicates
+    /// included).
+    pub fn with_header(mut self, header: Header) -> Response<R> {
+        self.headers.retain(|existing| existing.field() != header.field());
+        self.headers.push(header);
+        self
+    }
+
+    pub fn with_status_code<S: Into<StatusCode>>(mut self, code: S) -> Response<R> {
+        self.status_code = code.into();
+        self
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code.clone()
+    }
+
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    pub fn data_length(&self) -> Option<usize> {
+        self.data_length
+    }
+
+    /// Consumes the response and returns its body reader.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Boxes the body reader, discarding the concrete reader type.
+    pub fn boxed(self) -> Response<Box<dyn Read + Send>> where R: Send + 'static {
+        Response {
+            reader: Box::new(self.reader),
+            status_code: self.status_code,
+            headers: self.headers,
+            data_length: self.data_length,
+            chunked_threshold: self.chunked_threshold,
+        }
+    }
+
+    /// Writes the status line, headers and body to `writer`.
+    pub fn write(mut self, writer: &mut dyn Write, http_version: &HTTPVersion) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/{} {} {}\r\n",
+            http_version,
+            self.status_code.as_u16(),
+            self.status_code.get_default_reason_phrase()
+        )?;
+
+        let chunked = self.data_length.is_none();
+
+        for header in &self.headers {
+            write!(writer, "{}\r\n", header)?;
+        }
+
+        if let Some(length) = self.data_length {
+            write!(writer, "Content-Length: {}\r\n", length)?;
+        }
+
+        if chunked {
+            write!(writer, "Transfer-Encoding: chunked\r\n")?;
+        }
+
+        write!(writer, "\r\n")?;
+
+        if chunked {
+            write_chunked_body(&mut self.reader, writer)
+        } else {
+            io::copy(&mut self.reader, writer)?;
+            Ok(())
+        }
+    }
+}
+
+fn write_chunked_body(reader: &mut dyn Read, writer: &mut dyn Write) -> io::Result<()> {
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+
+        write!(writer, "{:x}\r\n", read)?;
+        writer.write_all(&buffer[..read])?;
+        write!(writer, "\r\n")?;
+    }
+
+    write!(writer, "0\r\n\r\n")
+}
+
+impl Response<Empty> {
+    /// Builds an empty response with the given status code.
+    pub fn new_empty(status_code: StatusCode) -> Response<Empty> {
+        Response::new(status_code, Vec::new(), io::empty(), Some(0), None)
+    }
+}
+
+impl Response<File> {
+    /// Builds a response that streams the contents of `file`, using its
+    /// metadata for `Content-Length`.
+    pub fn from_file(file: File) -> Response<File> {
+        let data_length = file.metadata().ok().map(|meta| meta.len() as usize);
+        Response::new(StatusCode(200), Vec::new(), file, data_length, None)
+    }
+}
+
+impl Response<io::Cursor<Vec<u8>>> {
+    /// Builds a response from an in-memory byte buffer.
+    pub fn from_data<D: Into<Vec<u8>>>(data: D) -> Response<io::Cursor<Vec<u8>>> {
+        let data = data.into();
+        let data_length = data.len();
+        Response::new(StatusCode(200), Vec::new(), io::Cursor::new(data), Some(data_length), None)
+    }
+
+    /// Builds a response from a `String`, tagging it `text/plain; charset=utf-8`.
+    pub fn from_string(data: String) -> Response<io::Cursor<Vec<u8>>> {
+        Response::from_data(data.into_bytes()).with_header(
+            Header::from_bytes("Content-Type".parse().unwrap(), "text/plain; charset=utf-8").unwrap(),
+        )
+    }
+}
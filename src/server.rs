@@ -0,0 +1,165 @@
+// Copyright 2015 The tiny-http Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{self, BufRead, BufReader};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+
+use common::{Header, HTTPVersion, Method};
+use request::Request;
+
+/// Builds a `Server`.
+pub struct ServerBuilder {
+    port: u16,
+}
+
+impl Default for ServerBuilder {
+    fn default() -> ServerBuilder {
+        ServerBuilder::new()
+    }
+}
+
+impl ServerBuilder {
+    pub fn new() -> ServerBuilder {
+        ServerBuilder { port: 0 }
+    }
+
+    pub fn with_port(mut self, port: u16) -> ServerBuilder {
+        self.port = port;
+        self
+    }
+
+    pub fn with_random_port(mut self) -> ServerBuilder {
+        self.port = 0;
+        self
+    }
+
+    pub fn build(self) -> io::Result<Server> {
+        let mut addrs = ("0.0.0.0", self.port).to_socket_addrs()?;
+
+        let addr = match addrs.next() {
+            Some(addr) => addr,
+            None => return Err(io::Error::other("could not resolve a listening address")),
+        };
+
+        let listener = TcpListener::bind(addr)?;
+
+        Ok(Server { listener })
+    }
+}
+
+/// A listening HTTP server.
+pub struct Server {
+    listener: TcpListener,
+}
+
+impl Server {
+    pub fn get_server_addr(&self) -> SocketAddr {
+        self.listener.local_addr().expect("the listening socket has no local address")
+    }
+
+    /// Blocks until the next request comes in.
+    pub fn recv(&self) -> io::Result<Request> {
+        let (stream, remote_addr) = self.listener.accept()?;
+        parse_request(stream, remote_addr)
+    }
+
+    /// An endless iterator over incoming requests; stops only once the
+    /// underlying socket errors out.
+    pub fn incoming_requests(&self) -> IncomingRequests<'_> {
+        IncomingRequests { server: self }
+    }
+}
+
+pub struct IncomingRequests<'a> {
+    server: &'a Server,
+}
+
+impl<'a> Iterator for IncomingRequests<'a> {
+    type Item = Request;
+
+    fn next(&mut self) -> Option<Request> {
+        loop {
+            let (stream, remote_addr) = match self.server.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(_) => return None,
+            };
+
+            // A request that fails to parse only drops its own connection.
+            if let Ok(request) = parse_request(stream, remote_addr) {
+                return Some(request);
+            }
+        }
+    }
+}
+
+fn parse_request(stream: TcpStream, remote_addr: SocketAddr) -> io::Result<Request> {
+    let writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut parts = request_line.trim_end().splitn(3, ' ');
+    let method = parts.next().unwrap_or("");
+    let url = parts.next().unwrap_or("/");
+    let version = parts.next().unwrap_or("HTTP/1.1");
+
+    let method: Method = match method.parse() {
+        Ok(m) => m,
+        Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid request method")),
+    };
+
+    let http_version = parse_http_version(version).unwrap_or(HTTPVersion(1, 1));
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if trimmed.is_empty() {
+            break;
+        }
+
+        if let Ok(header) = trimmed.parse::<Header>() {
+            headers.push(header);
+        }
+    }
+
+    let body_length = headers.iter()
+        .find(|header| header.field().equiv("Content-Length"))
+        .and_then(|header| header.value().trim().parse::<usize>().ok());
+
+    Ok(Request::new(
+        method,
+        url.to_string(),
+        headers,
+        http_version,
+        remote_addr,
+        body_length,
+        Box::new(reader),
+        Box::new(writer),
+    ))
+}
+
+/// Parses `"HTTP/1.1"` into `HTTPVersion(1, 1)`.
+fn parse_http_version(value: &str) -> Option<HTTPVersion> {
+    let version = value.strip_prefix("HTTP/")?;
+    let mut parts = version.splitn(2, '.');
+
+    let major = parts.next().and_then(|p| p.parse().ok())?;
+    let minor = parts.next().and_then(|p| p.parse().ok())?;
+
+    Some(HTTPVersion(major, minor))
+}
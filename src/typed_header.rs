@@ -0,0 +1,347 @@
+// Copyright 2015 The tiny-http Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validated, name-keyed access to common headers, layered on top of the
+//! loose `Header` string pairs.
+//!
+//! `Header` never goes away; `TypedHeader` just saves every caller from
+//! re-implementing the same by-hand parsing of `Content-Type`,
+//! `Content-Length`, and friends.
+
+use std::fmt;
+use std::num::ParseIntError;
+
+use common::{Header, HeaderField, HttpGrammarError};
+
+/// A header with a well-known name and a typed value.
+pub trait TypedHeader: Sized {
+    /// The header's field name, e.g. `"Content-Type"`.
+    const NAME: &'static str;
+
+    /// Parses the raw header value into this type.
+    fn parse(value: &str) -> Result<Self, TypedHeaderError>;
+
+    /// Renders this value back into the wire format for `Header::value`.
+    fn to_value(&self) -> String;
+
+    /// Builds the `Header` that carries this value.
+    ///
+    /// Goes through `Header::from_bytes` (see its doc for why) so a
+    /// `TypedHeader` wrapping attacker-controlled input (e.g. `ContentType`,
+    /// `Host`) can't smuggle a second header via an embedded CR/LF in
+    /// `to_value()` — such a value is rejected here rather than injected.
+    fn to_header(&self) -> Result<Header, TypedHeaderError> {
+        let field = Self::NAME.parse::<HeaderField>().unwrap();
+        Header::from_bytes(field, &self.to_value()).map_err(TypedHeaderError::from)
+    }
+}
+
+/// Why a `TypedHeader::parse` or `TypedHeader::to_header` call failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypedHeaderError {
+    /// The value isn't a valid instance of this header (e.g. a
+    /// non-numeric `Content-Length`).
+    Malformed,
+    /// The value doesn't conform to the HTTP header-value grammar (see
+    /// `HttpGrammarError`).
+    InvalidGrammar,
+}
+
+impl fmt::Display for TypedHeaderError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TypedHeaderError::Malformed => write!(formatter, "malformed header value"),
+            TypedHeaderError::InvalidGrammar => write!(formatter, "value does not conform to the HTTP grammar"),
+        }
+    }
+}
+
+impl From<HttpGrammarError> for TypedHeaderError {
+    fn from(_: HttpGrammarError) -> TypedHeaderError {
+        TypedHeaderError::InvalidGrammar
+    }
+}
+
+impl From<ParseIntError> for TypedHeaderError {
+    fn from(_: ParseIntError) -> TypedHeaderError {
+        TypedHeaderError::Malformed
+    }
+}
+
+/// `Content-Type: text/html; charset=utf-8`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentType(pub String);
+
+impl TypedHeader for ContentType {
+    const NAME: &'static str = "Content-Type";
+
+    fn parse(value: &str) -> Result<ContentType, TypedHeaderError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(TypedHeaderError::Malformed);
+        }
+        Ok(ContentType(value.to_string()))
+    }
+
+    fn to_value(&self) -> String {
+        self.0.clone()
+    }
+}
+
+/// `Content-Length: 1234`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLength(pub usize);
+
+impl TypedHeader for ContentLength {
+    const NAME: &'static str = "Content-Length";
+
+    fn parse(value: &str) -> Result<ContentLength, TypedHeaderError> {
+        Ok(ContentLength(value.trim().parse()?))
+    }
+
+    fn to_value(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// `Content-Encoding: gzip`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentEncoding(pub String);
+
+impl TypedHeader for ContentEncoding {
+    const NAME: &'static str = "Content-Encoding";
+
+    fn parse(value: &str) -> Result<ContentEncoding, TypedHeaderError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(TypedHeaderError::Malformed);
+        }
+        Ok(ContentEncoding(value.to_ascii_lowercase()))
+    }
+
+    fn to_value(&self) -> String {
+        self.0.clone()
+    }
+}
+
+/// `Host: example.com:8080`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host(pub String);
+
+impl TypedHeader for Host {
+    const NAME: &'static str = "Host";
+
+    fn parse(value: &str) -> Result<Host, TypedHeaderError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(TypedHeaderError::Malformed);
+        }
+        Ok(Host(value.to_string()))
+    }
+
+    fn to_value(&self) -> String {
+        self.0.clone()
+    }
+}
+
+/// `Connection: keep-alive` / `Connection: close`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connection {
+    KeepAlive,
+    Close,
+}
+
+impl TypedHeader for Connection {
+    const NAME: &'static str = "Connection";
+
+    fn parse(value: &str) -> Result<Connection, TypedHeaderError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "close" => Ok(Connection::Close),
+            "keep-alive" => Ok(Connection::KeepAlive),
+            _ => Err(TypedHeaderError::Malformed),
+        }
+    }
+
+    fn to_value(&self) -> String {
+        match *self {
+            Connection::KeepAlive => "keep-alive".to_string(),
+            Connection::Close => "close".to_string(),
+        }
+    }
+}
+
+/// `Transfer-Encoding: chunked`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferEncoding {
+    Chunked,
+    Identity,
+}
+
+impl TypedHeader for TransferEncoding {
+    const NAME: &'static str = "Transfer-Encoding";
+
+    fn parse(value: &str) -> Result<TransferEncoding, TypedHeaderError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "chunked" => Ok(TransferEncoding::Chunked),
+            "identity" => Ok(TransferEncoding::Identity),
+            _ => Err(TypedHeaderError::Malformed),
+        }
+    }
+
+    fn to_value(&self) -> String {
+        match *self {
+            TransferEncoding::Chunked => "chunked".to_string(),
+            TransferEncoding::Identity => "identity".to_string(),
+        }
+    }
+}
+
+/// One entry of a parsed `Accept-Encoding` list. `AcceptEncoding::parse`
+/// sorts entries by quality; entries of equal quality keep the order the
+/// client sent them in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptEncodingItem {
+    pub coding: String,
+    pub quality: f32,
+}
+
+/// `Accept-Encoding: gzip;q=1.0, identity; q=0.5, *;q=0`, quality-ordered
+/// highest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptEncoding(pub Vec<AcceptEncodingItem>);
+
+impl TypedHeader for AcceptEncoding {
+    const NAME: &'static str = "Accept-Encoding";
+
+    fn parse(value: &str) -> Result<AcceptEncoding, TypedHeaderError> {
+        let mut items = Vec::new();
+
+        for item in value.split(',') {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+
+            let mut parts = item.splitn(2, ';');
+            let coding = parts.next().unwrap().trim().to_ascii_lowercase();
+            if coding.is_empty() {
+                return Err(TypedHeaderError::Malformed);
+            }
+
+            let quality = match parts.next() {
+                Some(param) => {
+                    let param = match param.trim().strip_prefix("q=") {
+                        Some(param) => param,
+                        None => return Err(TypedHeaderError::Malformed),
+                    };
+                    match param.parse::<f32>() {
+                        Ok(q) => q,
+                        Err(_) => return Err(TypedHeaderError::Malformed),
+                    }
+                }
+                None => 1.0,
+            };
+
+            items.push(AcceptEncodingItem { coding, quality });
+        }
+
+        items.sort_by(|a, b| b.quality.partial_cmp(&a.quality).unwrap_or(::std::cmp::Ordering::Equal));
+
+        Ok(AcceptEncoding(items))
+    }
+
+    fn to_value(&self) -> String {
+        self.0
+            .iter()
+            .map(|item| format!("{};q={}", item.coding, item.quality))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+use Request;
+use Response;
+use std::io::Read;
+
+impl Request {
+    /// Looks up and parses the first header named `T::NAME`, if present.
+    pub fn header<T: TypedHeader>(&self) -> Option<Result<T, TypedHeaderError>> {
+        self.headers()
+            .iter()
+            .find(|h| h.field().equiv(T::NAME))
+            .map(|h| T::parse(h.value()))
+    }
+}
+
+impl<R> Response<R>
+where
+    R: Read,
+{
+    /// Attaches a typed header, replacing any existing header of the same
+    /// name (`with_header` drops any prior header with that name before
+    /// pushing this one, so stacking e.g. two `ContentEncoding`s can't
+    /// produce duplicate headers).
+    ///
+    /// Returns the response unchanged if `header` fails to round-trip
+    /// through `TypedHeader::to_header` (e.g. a `Host` or `ContentType`
+    /// wrapping a value with an embedded CR/LF); callers that need to
+    /// detect that case should call `header.to_header()` themselves.
+    pub fn with_typed_header<T: TypedHeader>(self, header: T) -> Response<R> {
+        match header.to_header() {
+            Ok(header) => self.with_header(header),
+            Err(_) => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AcceptEncoding, ContentLength, ContentType, TypedHeader};
+
+    #[test]
+    fn parses_content_length() {
+        assert_eq!(ContentLength::parse("42").unwrap(), ContentLength(42));
+        assert!(ContentLength::parse("forty-two").is_err());
+    }
+
+    #[test]
+    fn parses_content_type() {
+        assert_eq!(
+            ContentType::parse("text/html; charset=utf-8").unwrap(),
+            ContentType("text/html; charset=utf-8".to_string())
+        );
+    }
+
+    #[test]
+    fn accept_encoding_is_quality_sorted() {
+        let parsed = AcceptEncoding::parse("gzip;q=0.5, br, deflate;q=0.8").unwrap();
+        let codings: Vec<&str> = parsed.0.iter().map(|i| i.coding.as_str()).collect();
+        assert_eq!(codings, vec!["br", "deflate", "gzip"]);
+    }
+
+    #[test]
+    fn round_trips_to_value() {
+        let header = ContentLength(42).to_header().unwrap();
+        assert!(header.field().equiv("content-length"));
+        assert_eq!(header.value(), "42");
+    }
+
+    #[test]
+    fn to_header_rejects_smuggled_crlf() {
+        use super::TypedHeaderError;
+
+        let err = ContentType("text/html\r\nX-Injected: evil".to_string()).to_header().unwrap_err();
+        assert_eq!(err, TypedHeaderError::InvalidGrammar);
+    }
+}